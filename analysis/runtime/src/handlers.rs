@@ -100,6 +100,106 @@ pub fn offset(mir_loc: MirLocId, ptr: usize, offset: isize, new_ptr: usize) {
     });
 }
 
+/// A hook function (see [`CORE_HOOK_FUNCTIONS`]).
+///
+/// Instruments [`pointer::add`](https://doc.rust-lang.org/std/primitive.pointer.html#method.add),
+/// which is the same as [`offset`] with a non-negative `count`.
+pub fn add(mir_loc: MirLocId, ptr: usize, count: usize, new_ptr: usize) {
+    offset(mir_loc, ptr, count as isize, new_ptr);
+}
+
+/// A hook function (see [`CORE_HOOK_FUNCTIONS`]).
+///
+/// Instruments [`pointer::sub`](https://doc.rust-lang.org/std/primitive.pointer.html#method.sub),
+/// which is the same as [`offset`] with a non-positive `count`.
+pub fn sub(mir_loc: MirLocId, ptr: usize, count: usize, new_ptr: usize) {
+    offset(mir_loc, ptr, (count as isize).wrapping_neg(), new_ptr);
+}
+
+/// A hook function (see [`CORE_HOOK_FUNCTIONS`]).
+///
+/// Instruments [`pointer::wrapping_offset`](https://doc.rust-lang.org/std/primitive.pointer.html#method.wrapping_offset),
+/// which is recorded the same as [`offset`].
+pub fn wrapping_offset(mir_loc: MirLocId, ptr: usize, count: isize, new_ptr: usize) {
+    offset(mir_loc, ptr, count, new_ptr);
+}
+
+/// A hook function (see [`CORE_HOOK_FUNCTIONS`]).
+///
+/// Instruments [`pointer::wrapping_add`](https://doc.rust-lang.org/std/primitive.pointer.html#method.wrapping_add),
+/// which is recorded the same as [`add`].
+pub fn wrapping_add(mir_loc: MirLocId, ptr: usize, count: usize, new_ptr: usize) {
+    add(mir_loc, ptr, count, new_ptr);
+}
+
+/// A hook function (see [`CORE_HOOK_FUNCTIONS`]).
+///
+/// Instruments [`pointer::wrapping_sub`](https://doc.rust-lang.org/std/primitive.pointer.html#method.wrapping_sub),
+/// which is recorded the same as [`sub`].
+pub fn wrapping_sub(mir_loc: MirLocId, ptr: usize, count: usize, new_ptr: usize) {
+    sub(mir_loc, ptr, count, new_ptr);
+}
+
+/// A hook function (see [`CORE_HOOK_FUNCTIONS`]).
+///
+/// Instruments [`core::ptr::copy_nonoverlapping`], which loads from `src` and stores to `dst`,
+/// unless `count` is 0, in which case neither is accessed.
+pub fn copy_nonoverlapping(mir_loc: MirLocId, src: usize, dst: usize, count: usize, _ret: ()) {
+    if count == 0 {
+        return;
+    }
+    RUNTIME.send_event(Event {
+        mir_loc,
+        kind: EventKind::LoadAddr(src),
+    });
+    RUNTIME.send_event(Event {
+        mir_loc,
+        kind: EventKind::StoreAddr(dst),
+    });
+}
+
+/// A hook function (see [`CORE_HOOK_FUNCTIONS`]).
+///
+/// Instruments [`core::ptr::write_bytes`], which stores to `dst`,
+/// unless `count` is 0, in which case it isn't accessed.
+pub fn write_bytes(mir_loc: MirLocId, dst: usize, _val: u8, count: usize, _ret: ()) {
+    if count == 0 {
+        return;
+    }
+    RUNTIME.send_event(Event {
+        mir_loc,
+        kind: EventKind::StoreAddr(dst),
+    });
+}
+
+/// A hook function (see [`CORE_HOOK_FUNCTIONS`]).
+///
+/// Instruments [`core::slice::from_raw_parts`], which copies `data` into the returned `slice`.
+pub fn from_raw_parts(mir_loc: MirLocId, _data: usize, _len: usize, slice: usize) {
+    ptr_copy(mir_loc, slice);
+}
+
+/// A hook function (see [`CORE_HOOK_FUNCTIONS`]).
+///
+/// Instruments [`core::slice::from_raw_parts_mut`], which copies `data` into the returned `slice`.
+pub fn from_raw_parts_mut(mir_loc: MirLocId, _data: usize, _len: usize, slice: usize) {
+    ptr_copy(mir_loc, slice);
+}
+
+/// A hook function (see [`CORE_HOOK_FUNCTIONS`]).
+///
+/// Instruments [`core::ptr::NonNull::new_unchecked`], which copies `ptr` into the returned [`NonNull`](core::ptr::NonNull).
+pub fn new_unchecked(mir_loc: MirLocId, _ptr: usize, non_null: usize) {
+    ptr_copy(mir_loc, non_null);
+}
+
+/// A hook function (see [`CORE_HOOK_FUNCTIONS`]).
+///
+/// Instruments [`core::ptr::NonNull::as_ptr`], which copies the [`NonNull`](core::ptr::NonNull) into the returned `ptr`.
+pub fn as_ptr(mir_loc: MirLocId, _non_null: usize, ptr: usize) {
+    ptr_copy(mir_loc, ptr);
+}
+
 macro_rules! hook_fn {
     ($name:ident) => {{
         // Ensure it exists and allow rust-analyzer to see through it.
@@ -122,6 +222,26 @@ pub const HOOK_FUNCTIONS: &[&str] = &[
     hook_fn!(offset),
 ];
 
+/// List of functions and methods from `core` we want hooked for the lifetime analysis runtime.
+///
+/// Unlike [`HOOK_FUNCTIONS`], which are hooked wherever a function with that name is called,
+/// these names are too common, so they are only hooked when called on `core`'s
+/// raw pointers, [`NonNull`](core::ptr::NonNull)s, or in its `ptr`, `slice`, and `intrinsics` modules.
+/// The return value is passed as the last argument, as for [`HOOK_FUNCTIONS`].
+pub const CORE_HOOK_FUNCTIONS: &[&str] = &[
+    hook_fn!(add),
+    hook_fn!(sub),
+    hook_fn!(wrapping_offset),
+    hook_fn!(wrapping_add),
+    hook_fn!(wrapping_sub),
+    hook_fn!(copy_nonoverlapping),
+    hook_fn!(write_bytes),
+    hook_fn!(from_raw_parts),
+    hook_fn!(from_raw_parts_mut),
+    hook_fn!(new_unchecked),
+    hook_fn!(as_ptr),
+];
+
 pub fn ptr_project(mir_loc: MirLocId, ptr: usize, new_ptr: usize, proj_key: u64) {
     RUNTIME.send_event(Event {
         mir_loc,
@@ -199,6 +199,9 @@ pub struct EventMetadata {
     pub transfer_kind: TransferKind,
     /// Any string useful for debugging.
     pub debug_info: String,
+    /// Source location (`file:line:col: line:col`) of the original statement.
+    #[serde(default)]
+    pub span: String,
 }
 
 impl EventMetadata {
@@ -208,6 +211,7 @@ impl EventMetadata {
             destination,
             transfer_kind,
             debug_info: _,
+            span: _,
         } = self;
         (source, destination, transfer_kind)
     }
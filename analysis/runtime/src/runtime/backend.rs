@@ -2,8 +2,10 @@ use crossbeam_queue::ArrayQueue;
 use crossbeam_utils::Backoff;
 use enum_dispatch::enum_dispatch;
 use fs_err::{File, OpenOptions};
-use std::fmt::Debug;
-use std::io::{stderr, BufWriter, Write};
+use std::fmt::{self, Debug};
+use std::io::{self, stderr, BufWriter, Write};
+use std::os::unix::ffi::OsStringExt;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
 use std::sync::Arc;
 
 use bincode;
@@ -165,3 +167,253 @@ impl Detect for Backend {
         Self::detect_kind(BackendKind::detect()?)
     }
 }
+
+/// A fixed-size buffer, for formatting without allocating.
+struct StackBuf<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> StackBuf<N> {
+    fn new() -> Self {
+        Self {
+            buf: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Append as much of `bytes` as fits, returning whether all of it did.
+    fn push(&mut self, bytes: &[u8]) -> bool {
+        let n = bytes.len().min(N - self.len);
+        self.buf[self.len..self.len + n].copy_from_slice(&bytes[..n]);
+        self.len += n;
+        n == bytes.len()
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl<const N: usize> Write for StackBuf<N> {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        let len = self.len;
+        self.push(bytes);
+        Ok(self.len - len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<const N: usize> fmt::Write for StackBuf<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push(s.as_bytes()).then_some(()).ok_or(fmt::Error)
+    }
+}
+
+/// Write all of `bytes` to `fd`, giving up on errors, as there's nowhere to report them.
+///
+/// # Async-signal-safety: safe, as this is only `write(2)`.
+fn write_fd(fd: libc::c_int, mut bytes: &[u8]) {
+    while !bytes.is_empty() {
+        // Safety: `bytes` is valid for `bytes.len()` bytes.
+        let n = unsafe { libc::write(fd, bytes.as_ptr().cast(), bytes.len()) };
+        if n < 0 {
+            if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return;
+        }
+        bytes = &bytes[n as usize..];
+    }
+}
+
+/// The longest serialized [`Event`]: a [`MirLocId`](crate::mir_loc::MirLocId),
+/// an [`EventKind`] tag, and at most 3 8-byte fields.
+const MAX_EVENT_LEN: usize = 64;
+
+/// The longest line a `fork`ed child writes for [`BackendKind::Debug`]; longer ones are truncated.
+const MAX_DEBUG_LINE_LEN: usize = 1024;
+
+const PATH_MAX: usize = libc::PATH_MAX as usize;
+
+/// Where a [`ForkedBackend`] writes.
+enum ForkedOutput {
+    /// Like [`DebugBackend`], to `stderr`.
+    Debug(DebugBackend),
+    /// Like [`LogBackend`], to `$INSTRUMENT_OUTPUT.{pid}`.
+    Log {
+        /// `$INSTRUMENT_OUTPUT.`, to which the child's pid is appended.
+        path_prefix: Vec<u8>,
+        append: bool,
+    },
+}
+
+/// The [`Backend`] of a `fork`ed child of a multithreaded process,
+/// which may only do async-signal-safe things:
+/// it can't allocate, spawn a writer thread, or use [`std::io`]'s locks.
+///
+/// So it is detected in the parent, before any `fork`,
+/// and in the child, each [`Event`] is written by the thread sending it, with `write(2)`.
+/// A child's log is only created by its first [`Event`],
+/// so children that go straight to `exec` don't get one.
+pub(super) struct ForkedBackend {
+    output: ForkedOutput,
+    /// The fd being written to, or -1 if the child's log hasn't been opened yet.
+    fd: AtomicI32,
+    finalized: AtomicBool,
+}
+
+impl ForkedBackend {
+    pub(super) fn detect_kind(kind: BackendKind) -> Result<Self, AnyError> {
+        let (output, fd) = match kind {
+            BackendKind::Debug => (
+                ForkedOutput::Debug(DebugBackend::detect()?),
+                libc::STDERR_FILENO,
+            ),
+            BackendKind::Log => {
+                let append: bool = *parse::env::one_of("INSTRUMENT_OUTPUT_APPEND")?;
+                let mut path_prefix = parse::env::path("INSTRUMENT_OUTPUT")?
+                    .into_os_string()
+                    .into_vec();
+                path_prefix.push(b'.');
+                // Leave room for the pid and the nul terminator.
+                if path_prefix.contains(&0) || path_prefix.len() + 21 > PATH_MAX {
+                    return Err("$INSTRUMENT_OUTPUT is not a valid path for a per-PID log".into());
+                }
+                (
+                    ForkedOutput::Log {
+                        path_prefix,
+                        append,
+                    },
+                    -1,
+                )
+            }
+        };
+        Ok(Self {
+            output,
+            fd: AtomicI32::new(fd),
+            finalized: AtomicBool::new(false),
+        })
+    }
+
+    /// The nul-terminated `$INSTRUMENT_OUTPUT.{pid}` of the current process.
+    fn log_path(path_prefix: &[u8]) -> StackBuf<PATH_MAX> {
+        let mut path = StackBuf::new();
+        path.push(path_prefix);
+        // Safety: `getpid` is always safe.
+        let pid = unsafe { libc::getpid() };
+        // This fits, as checked in [`ForkedBackend::detect_kind`].
+        let _ = fmt::Write::write_fmt(&mut path, format_args!("{pid}\0"));
+        path
+    }
+
+    /// Reset the [`ForkedBackend`] for a newly `fork`ed child.
+    ///
+    /// This runs in a `pthread_atfork` child handler, before any other thread exists in the child.
+    /// It doesn't create the child's log, but it removes a stale one unless appending,
+    /// as the log is opened for appending so that it can be opened by whichever thread sends first.
+    ///
+    /// # Async-signal-safety: safe, as this is only atomics, `close(2)`, and `unlink(2)`.
+    pub(super) fn after_fork(&self) {
+        self.finalized.store(false, Ordering::Relaxed);
+        if let ForkedOutput::Log {
+            path_prefix,
+            append,
+        } = &self.output
+        {
+            // A grandchild inherits its parent's log, which it must not write to.
+            let fd = self.fd.swap(-1, Ordering::Relaxed);
+            // Safety: `fd` is our own copy of the parent's log fd.
+            if fd >= 0 {
+                unsafe { libc::close(fd) };
+            }
+            if !append {
+                let path = Self::log_path(path_prefix);
+                // Safety: `path` is nul-terminated.
+                unsafe { libc::unlink(path.as_bytes().as_ptr().cast()) };
+            }
+        }
+    }
+
+    /// The fd to write to, opening the child's log if this is its first [`Event`].
+    ///
+    /// # Async-signal-safety: safe, as this is only atomics, `open(2)`, and `close(2)`.
+    fn fd(&self) -> Option<libc::c_int> {
+        let fd = self.fd.load(Ordering::Acquire);
+        if fd >= 0 {
+            return Some(fd);
+        }
+        let path_prefix = match &self.output {
+            ForkedOutput::Log { path_prefix, .. } => path_prefix,
+            ForkedOutput::Debug(_) => return None,
+        };
+        let path = Self::log_path(path_prefix);
+        let flags = libc::O_WRONLY | libc::O_CREAT | libc::O_APPEND | libc::O_CLOEXEC;
+        // Safety: `path` is nul-terminated.
+        let new_fd = unsafe { libc::open(path.as_bytes().as_ptr().cast(), flags, 0o666) };
+        if new_fd < 0 {
+            return None;
+        }
+        // Another thread may have opened the log first.
+        match self
+            .fd
+            .compare_exchange(-1, new_fd, Ordering::AcqRel, Ordering::Acquire)
+        {
+            Ok(_) => Some(new_fd),
+            Err(fd) => {
+                // Safety: `new_fd` was opened above and not shared.
+                unsafe { libc::close(new_fd) };
+                Some(fd)
+            }
+        }
+    }
+
+    fn write_unchecked(&self, event: &Event) {
+        match &self.output {
+            ForkedOutput::Debug(debug) => {
+                let mut line = StackBuf::<MAX_DEBUG_LINE_LEN>::new();
+                let mir_loc = debug.metadata.get(event.mir_loc);
+                let _ = fmt::Write::write_fmt(
+                    &mut line,
+                    format_args!("{:?}: {:?}", mir_loc, event.kind),
+                );
+                line.len = line.len.min(MAX_DEBUG_LINE_LEN - 1);
+                line.push(b"\n");
+                write_fd(libc::STDERR_FILENO, line.as_bytes());
+            }
+            ForkedOutput::Log { .. } => {
+                let mut bytes = StackBuf::<MAX_EVENT_LEN>::new();
+                bincode::serialize_into(&mut bytes, event).unwrap();
+                // A single `write(2)` with `O_APPEND`, so events from different threads don't interleave.
+                if let Some(fd) = self.fd() {
+                    write_fd(fd, bytes.as_bytes());
+                }
+            }
+        }
+    }
+
+    /// Write an [`Event`], unless already [`ForkedBackend::finalize`]d.
+    ///
+    /// # Async-signal-safety: safe, as this only formats into stack buffers
+    /// and does the syscalls of [`ForkedBackend::fd`] and `write(2)`.
+    pub(super) fn write(&self, event: &Event) {
+        if !self.finalized.load(Ordering::Acquire) {
+            self.write_unchecked(event);
+        }
+    }
+
+    /// Write [`Event::done`], if the child has written anything.
+    ///
+    /// Must be idempotent.
+    pub(super) fn finalize(&self) {
+        if self.finalized.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        if self.fd.load(Ordering::Acquire) >= 0 {
+            self.write_unchecked(&Event::done());
+        }
+    }
+}
@@ -1,5 +1,9 @@
 use std::{
-    sync::{Arc, Mutex},
+    ptr,
+    sync::{
+        atomic::{AtomicBool, AtomicPtr, Ordering},
+        Arc, Mutex,
+    },
     thread,
 };
 
@@ -14,7 +18,7 @@ use crate::{
 };
 
 use super::{
-    backend::{Backend, WriteEvent},
+    backend::{Backend, BackendKind, ForkedBackend, WriteEvent},
     skip::{skip_event, SkipReason},
     AnyError, Detect, FINISHED,
 };
@@ -23,6 +27,7 @@ use super::{
 pub enum RuntimeKind {
     MainThread,
     BackgroundThread,
+    ForkSafe,
 }
 
 impl AsStr for RuntimeKind {
@@ -30,13 +35,14 @@ impl AsStr for RuntimeKind {
         match self {
             Self::MainThread => "fg",
             Self::BackgroundThread => "bg",
+            Self::ForkSafe => "fork",
         }
     }
 }
 
 impl GetChoices for RuntimeKind {
     fn choices() -> &'static [Self] {
-        &[Self::MainThread, Self::BackgroundThread]
+        &[Self::MainThread, Self::BackgroundThread, Self::ForkSafe]
     }
 }
 
@@ -73,6 +79,7 @@ trait Runtime: ExistingRuntime + Sized {
 pub enum ScopedRuntime {
     MainThread(MainThreadRuntime),
     BackgroundThread(BackgroundThreadRuntime),
+    ForkSafe(ForkSafeRuntime),
 }
 
 impl ScopedRuntime {
@@ -83,6 +90,7 @@ impl ScopedRuntime {
             RuntimeKind::BackgroundThread => {
                 Self::BackgroundThread(BackgroundThreadRuntime::try_init(backend)?)
             }
+            RuntimeKind::ForkSafe => Self::ForkSafe(ForkSafeRuntime::try_init(backend)?),
         };
         Ok(this)
     }
@@ -122,23 +130,28 @@ pub struct BackgroundThreadRuntime {
     finalized: OnceCell<()>,
 }
 
-impl BackgroundThreadRuntime {
-    fn push_event(&self, mut event: Event, can_sleep: bool) {
-        // # Async-signal-safety: This needs `can_sleep == false` if called from
-        // a signal handler; in that case, it spins instead of sleeping
-        // which should be safe. `ArrayQueue::push` is backed by a fixed-size
-        // array so it does not allocate.
-        let backoff = Backoff::new();
-        while let Err(event_back) = self.tx.push(event) {
-            if can_sleep {
-                backoff.snooze();
-            } else {
-                // We have no choice but to spin here because
-                // we might be inside a signal handler
-                backoff.spin();
-            }
-            event = event_back;
+/// Push an [`Event`] onto a queue read by [`Backend::run`].
+fn push_event(events: &ArrayQueue<Event>, mut event: Event, can_sleep: bool) {
+    // # Async-signal-safety: This needs `can_sleep == false` if called from
+    // a signal handler; in that case, it spins instead of sleeping
+    // which should be safe. `ArrayQueue::push` is backed by a fixed-size
+    // array so it does not allocate.
+    let backoff = Backoff::new();
+    while let Err(event_back) = events.push(event) {
+        if can_sleep {
+            backoff.snooze();
+        } else {
+            // We have no choice but to spin here because
+            // we might be inside a signal handler
+            backoff.spin();
         }
+        event = event_back;
+    }
+}
+
+impl BackgroundThreadRuntime {
+    fn push_event(&self, event: Event, can_sleep: bool) {
+        push_event(&self.tx, event, can_sleep);
     }
 }
 
@@ -206,3 +219,158 @@ impl Runtime for BackgroundThreadRuntime {
         })
     }
 }
+
+/// The [`ForkSafeRuntime`] state of the process that initialized it.
+///
+/// This is leaked, as a signal handler may be using it at any time.
+struct ForkSafeWriter {
+    events: Arc<ArrayQueue<Event>>,
+    finalized: AtomicBool,
+    /// How `fork`ed children write their [`Event`]s instead.
+    forked: ForkedBackend,
+}
+
+/// The [`ForkSafeWriter`] of the current process, or null if there is no [`ForkSafeRuntime`].
+///
+/// This is a `static` so that the `pthread_atfork` and `atexit` handlers can reach it.
+static FORK_SAFE_WRITER: AtomicPtr<ForkSafeWriter> = AtomicPtr::new(ptr::null_mut());
+
+/// Set in a `fork`ed child, which writes with the [`ForkedBackend`] instead.
+/// See [`enter_fork_safe_runtime_child`].
+static FORKED: AtomicBool = AtomicBool::new(false);
+
+impl ForkSafeWriter {
+    fn current() -> Option<&'static Self> {
+        // Safety: non-null [`FORK_SAFE_WRITER`]s are leaked, so they live forever.
+        unsafe { FORK_SAFE_WRITER.load(Ordering::Acquire).as_ref() }
+    }
+
+    fn send_event(&self, event: Event) {
+        // A child's threads all start after the `pthread_atfork` handler set [`FORKED`],
+        // so this doesn't need to synchronize with anything.
+        if FORKED.load(Ordering::Relaxed) {
+            self.forked.write(&event);
+        } else if !self.finalized.load(Ordering::Acquire) {
+            push_event(&self.events, event, false);
+        }
+    }
+
+    fn finalize(&self) {
+        if FORKED.load(Ordering::Relaxed) {
+            self.forked.finalize();
+            return;
+        }
+        if self.finalized.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        push_event(&self.events, Event::done(), true);
+        let (lock, cvar) = &*FINISHED;
+        let mut finished = lock.lock().unwrap();
+        while !*finished {
+            finished = cvar.wait(finished).unwrap();
+        }
+    }
+}
+
+/// A `pthread_atfork` child handler that switches the child to the [`ForkedBackend`].
+///
+/// The child inherits its parent's queue, but not the thread writing it,
+/// so it writes its [`Event`]s itself instead.
+/// This runs before `fork` returns, so before the child can have any other threads.
+///
+/// # Async-signal-safety: safe, as this is only atomics and [`ForkedBackend::after_fork`].
+extern "C" fn enter_fork_safe_runtime_child() {
+    if let Some(writer) = ForkSafeWriter::current() {
+        writer.forked.after_fork();
+        FORKED.store(true, Ordering::Relaxed);
+    }
+}
+
+/// An `atexit` handler, so that `fork`ed children that `exit` without returning from `main` are finalized, too.
+extern "C" fn finalize_fork_safe_runtime_at_exit() {
+    if let Some(writer) = ForkSafeWriter::current() {
+        writer.finalize();
+    }
+}
+
+/// A runtime like [`BackgroundThreadRuntime`], but that also works in `fork`ed children
+/// and is guaranteed async-signal-safe.
+///
+/// After a `fork`, the child writes its own [`Event`]s (see [`ForkedBackend`]),
+/// to a per-PID log, `$INSTRUMENT_OUTPUT.{pid}`, for [`BackendKind::Log`].
+///
+/// Only one [`ForkSafeRuntime`] can be initialized per process.
+pub struct ForkSafeRuntime {
+    _private: (),
+}
+
+impl ForkSafeRuntime {
+    fn writer(&self) -> &'static ForkSafeWriter {
+        // A [`ForkSafeRuntime`] is only created once [`FORK_SAFE_WRITER`] is set,
+        // and it is never reset.
+        ForkSafeWriter::current().unwrap()
+    }
+}
+
+impl ExistingRuntime for ForkSafeRuntime {
+    fn finalize(&self) {
+        self.writer().finalize();
+    }
+
+    /// Send an [`Event`] to the [`ForkSafeRuntime`].
+    ///
+    /// If the [`ForkSafeRuntime`] has already been [`ForkSafeRuntime::finalize`]d,
+    /// then the [`Event`] is silently dropped, without [`skip_event`]'s warnings.
+    ///
+    /// # Async-signal-safety: safe, as this is only atomic loads
+    /// and either a `push_event` with `can_sleep == false`
+    /// or, in a `fork`ed child, a [`ForkedBackend::write`].
+    fn send_event(&self, event: Event) {
+        self.writer().send_event(event);
+    }
+}
+
+impl Drop for ForkSafeRuntime {
+    /// Finalize the [`ForkSafeRuntime`], shutting it down.
+    ///
+    /// This does the same thing as [`ForkSafeRuntime::finalize`].
+    fn drop(&mut self) {
+        self.finalize();
+    }
+}
+
+impl Runtime for ForkSafeRuntime {
+    /// Initialize the [`ForkSafeRuntime`], which includes [`thread::spawn`]ing,
+    /// so it must be run post-`main`.
+    fn try_init(mut backend: Backend) -> Result<Self, AnyError> {
+        if ForkSafeWriter::current().is_some() {
+            return Err("a fork-safe runtime was already initialized in this process".into());
+        }
+        let kind = match backend {
+            Backend::Debug(_) => BackendKind::Debug,
+            Backend::Log(_) => BackendKind::Log,
+        };
+        let writer: &'static ForkSafeWriter = Box::leak(Box::new(ForkSafeWriter {
+            events: Arc::new(ArrayQueue::new(1 << 20)),
+            finalized: AtomicBool::new(false),
+            forked: ForkedBackend::detect_kind(kind)?,
+        }));
+        let events = Arc::clone(&writer.events);
+        thread::spawn(move || backend.run(events));
+        FORK_SAFE_WRITER.store(writer as *const _ as *mut _, Ordering::Release);
+        // Safety: the handlers are `extern "C"` and only access `static`s.
+        let (atfork, atexit) = unsafe {
+            (
+                libc::pthread_atfork(None, None, Some(enter_fork_safe_runtime_child)),
+                libc::atexit(finalize_fork_safe_runtime_at_exit),
+            )
+        };
+        if atfork != 0 {
+            return Err(std::io::Error::from_raw_os_error(atfork).into());
+        }
+        if atexit != 0 {
+            return Err("failed to register the fork-safe runtime's `atexit` handler".into());
+        }
+        Ok(Self { _private: () })
+    }
+}
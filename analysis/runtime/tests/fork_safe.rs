@@ -0,0 +1,160 @@
+//! Tests for `INSTRUMENT_RUNTIME=fork`.
+//!
+//! These share the global runtime, so they must be the only tests in this binary.
+
+use std::{
+    env,
+    fs::File,
+    io::{BufReader, ErrorKind},
+    path::Path,
+    process, ptr, thread,
+};
+
+use c2rust_analysis_rt::{
+    events::{Event, EventKind},
+    mir_loc::MirLocId,
+    runtime::global_runtime::RUNTIME,
+};
+
+const BEFORE_FORK: MirLocId = 1;
+const SIGNAL: MirLocId = 2;
+const IN_CHILD: MirLocId = 3;
+const AFTER_FORK: MirLocId = 4;
+const IN_CHILD_THREAD: MirLocId = 5;
+
+/// The number of threads a child spawns, and how many events each sends.
+const CHILD_THREADS: usize = 4;
+const CHILD_THREAD_EVENTS: usize = 100;
+
+/// Send an [`Event`] tagged with the current process's id.
+fn send(mir_loc: MirLocId) {
+    RUNTIME.send_event(Event {
+        mir_loc,
+        kind: EventKind::LoadAddr(process::id() as usize),
+    });
+}
+
+extern "C" fn on_signal(_signal: libc::c_int) {
+    send(SIGNAL);
+}
+
+/// Read an event log, returning each [`Event`]'s [`MirLocId`] and the process id it is tagged with,
+/// or [`None`] for [`EventKind::Done`].
+fn read_event_log(path: &Path) -> Vec<(MirLocId, Option<u32>)> {
+    let mut reader = BufReader::new(File::open(path).unwrap());
+    let mut events = Vec::new();
+    loop {
+        let event: Event = match bincode::deserialize_from(&mut reader) {
+            Ok(event) => event,
+            Err(e) => match *e {
+                bincode::ErrorKind::Io(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+                e => panic!("{e}"),
+            },
+        };
+        let pid = match event.kind {
+            EventKind::LoadAddr(pid) => Some(pid as u32),
+            EventKind::Done => None,
+            kind => panic!("unexpected event: {kind:?}"),
+        };
+        events.push((event.mir_loc, pid));
+    }
+    events
+}
+
+#[test]
+fn fork_and_signal() {
+    let log_path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("fork_safe.log.bc");
+    env::set_var("INSTRUMENT_RUNTIME", "fork");
+    env::set_var("INSTRUMENT_BACKEND", "log");
+    env::set_var("INSTRUMENT_OUTPUT", &log_path);
+    env::set_var("INSTRUMENT_OUTPUT_APPEND", "false");
+    c2rust_analysis_rt::initialize();
+
+    // Safety: `on_signal` is async-signal-safe, which is what's being tested.
+    let handler = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    assert_ne!(
+        unsafe { libc::signal(libc::SIGUSR1, handler) },
+        libc::SIG_ERR
+    );
+
+    let parent = process::id();
+    send(BEFORE_FORK);
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR1) }, 0);
+
+    // Safety: the child only sends events from itself, a signal handler, and new threads,
+    // then finalizes and `_exit`s.
+    let child = match unsafe { libc::fork() } {
+        -1 => panic!("fork failed"),
+        0 => {
+            send(IN_CHILD);
+            unsafe { libc::raise(libc::SIGUSR1) };
+            let threads = (0..CHILD_THREADS)
+                .map(|_| {
+                    thread::spawn(|| {
+                        for _ in 0..CHILD_THREAD_EVENTS {
+                            send(IN_CHILD_THREAD);
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+            for thread in threads {
+                thread.join().unwrap();
+            }
+            c2rust_analysis_rt::finalize();
+            unsafe { libc::_exit(0) };
+        }
+        child => child,
+    };
+    let mut status = 0;
+    assert_eq!(unsafe { libc::waitpid(child, &mut status, 0) }, child);
+    assert!(libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0);
+    let child = child as u32;
+
+    // A child that goes straight to `exec` doesn't send any events, so it doesn't get a log.
+    // Safety: the child only `exec`s or `_exit`s.
+    let exec_child = match unsafe { libc::fork() } {
+        -1 => panic!("fork failed"),
+        0 => unsafe {
+            let true_path = b"/bin/true\0".as_ptr().cast();
+            libc::execl(true_path, true_path, ptr::null::<libc::c_char>());
+            libc::_exit(127);
+        },
+        child => child,
+    };
+    assert_eq!(
+        unsafe { libc::waitpid(exec_child, &mut status, 0) },
+        exec_child
+    );
+    assert!(libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0);
+
+    send(AFTER_FORK);
+    c2rust_analysis_rt::finalize();
+    // Events after finalization are silently dropped.
+    send(AFTER_FORK);
+
+    assert_eq!(
+        read_event_log(&log_path),
+        [
+            (BEFORE_FORK, Some(parent)),
+            (SIGNAL, Some(parent)),
+            (AFTER_FORK, Some(parent)),
+            (0, None),
+        ]
+    );
+    let mut child_log_path = log_path.clone().into_os_string();
+    child_log_path.push(format!(".{child}"));
+    let child_log = read_event_log(Path::new(&child_log_path));
+    let thread_events = CHILD_THREADS * CHILD_THREAD_EVENTS;
+    assert_eq!(child_log.len(), 3 + thread_events);
+    assert_eq!(
+        child_log[..2],
+        [(IN_CHILD, Some(child)), (SIGNAL, Some(child))]
+    );
+    assert!(child_log[2..2 + thread_events]
+        .iter()
+        .all(|&event| event == (IN_CHILD_THREAD, Some(child))));
+    assert_eq!(child_log.last(), Some(&(0, None)));
+    let mut exec_child_log_path = log_path.into_os_string();
+    exec_child_log_path.push(format!(".{exec_child}"));
+    assert!(!Path::new(&exec_child_log_path).exists());
+}
@@ -0,0 +1,82 @@
+//! Tests for the hooks of `core`'s pointer methods and intrinsics (see `CORE_HOOK_FUNCTIONS`).
+//!
+//! These share the global runtime, so they must be the only tests in this binary.
+
+use std::{
+    env,
+    fs::File,
+    io::{BufReader, ErrorKind},
+    path::Path,
+};
+
+use c2rust_analysis_rt::{
+    events::{Event, EventKind},
+    mir_loc::MirLocId,
+};
+
+/// Read an event log, returning each [`Event`]'s [`MirLocId`] and its [`EventKind`]'s [`Debug`] output.
+fn read_event_log(path: &Path) -> Vec<(MirLocId, String)> {
+    let mut reader = BufReader::new(File::open(path).unwrap());
+    let mut events = Vec::new();
+    loop {
+        let event: Event = match bincode::deserialize_from(&mut reader) {
+            Ok(event) => event,
+            Err(e) => match *e {
+                bincode::ErrorKind::Io(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+                e => panic!("{e}"),
+            },
+        };
+        if let EventKind::Done = event.kind {
+            continue;
+        }
+        events.push((event.mir_loc, format!("{:?}", event.kind)));
+    }
+    events
+}
+
+#[test]
+fn core_pointer_hooks() {
+    let log_path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("hooks.log.bc");
+    env::set_var("INSTRUMENT_RUNTIME", "bg");
+    env::set_var("INSTRUMENT_BACKEND", "log");
+    env::set_var("INSTRUMENT_OUTPUT", &log_path);
+    env::set_var("INSTRUMENT_OUTPUT_APPEND", "false");
+    c2rust_analysis_rt::initialize();
+
+    let p = 0x1000;
+    c2rust_analysis_rt::add(1, p, 2, p + 2);
+    c2rust_analysis_rt::sub(2, p + 2, 2, p);
+    c2rust_analysis_rt::wrapping_offset(3, p, -1, p - 1);
+    c2rust_analysis_rt::wrapping_add(4, p, 3, p + 3);
+    c2rust_analysis_rt::wrapping_sub(5, p, 3, p - 3);
+    c2rust_analysis_rt::copy_nonoverlapping(6, p, p + 8, 4, ());
+    // Empty copies and writes don't access memory.
+    c2rust_analysis_rt::copy_nonoverlapping(7, p, p + 8, 0, ());
+    c2rust_analysis_rt::write_bytes(8, p, 0, 4, ());
+    c2rust_analysis_rt::write_bytes(9, p, 0, 0, ());
+    c2rust_analysis_rt::from_raw_parts(10, p, 4, p);
+    c2rust_analysis_rt::from_raw_parts_mut(11, p, 4, p);
+    c2rust_analysis_rt::new_unchecked(12, p, p);
+    c2rust_analysis_rt::as_ptr(13, p, p);
+    // Offsets from a null base pointer are copies of the offset, as for `offset`.
+    c2rust_analysis_rt::add(14, 0, p, p);
+    c2rust_analysis_rt::finalize();
+
+    let expected = [
+        (1, "offset(0x1000, 2, 0x1002)"),
+        (2, "offset(0x1002, -2, 0x1000)"),
+        (3, "offset(0x1000, -1, 0xfff)"),
+        (4, "offset(0x1000, 3, 0x1003)"),
+        (5, "offset(0x1000, -3, 0xffd)"),
+        (6, "load(0x1000)"),
+        (6, "store(0x1008)"),
+        (8, "store(0x1000)"),
+        (10, "copy(0x1000)"),
+        (11, "copy(0x1000)"),
+        (12, "copy(0x1000)"),
+        (13, "copy(0x1000)"),
+        (14, "copy(0x1000)"),
+    ]
+    .map(|(mir_loc, kind)| (mir_loc, kind.to_owned()));
+    assert_eq!(read_event_log(&log_path), expected);
+}
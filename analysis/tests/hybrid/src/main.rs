@@ -0,0 +1,38 @@
+#![feature(rustc_private)]
+
+use std::ptr;
+
+pub unsafe extern "C" fn pick(use_null: bool, x: *mut i32) -> *mut i32 {
+    if use_null {
+        ptr::null_mut()
+    } else {
+        x
+    }
+}
+
+/// `p` might be null as far as the static analysis can tell, but it never is at runtime.
+pub unsafe extern "C" fn read(x: *mut i32) -> i32 {
+    let p = pick(false, x);
+    *p
+}
+
+pub unsafe extern "C" fn set_both(p: *mut i32, q: *mut i32) {
+    *p = 1;
+    *q = 2;
+}
+
+/// `set_both` might get the same pointer twice as far as the static analysis can tell, but it
+/// never does at runtime.
+pub unsafe extern "C" fn set_maybe_aliased(alias: bool, x: *mut i32, y: *mut i32) {
+    let q = if alias { x } else { y };
+    set_both(x, q);
+}
+
+fn main() {
+    let mut x = 1;
+    let mut y = 2;
+    unsafe {
+        read(&mut x);
+        set_maybe_aliased(false, &mut x, &mut y);
+    }
+}
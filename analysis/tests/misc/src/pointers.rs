@@ -631,6 +631,22 @@ pub unsafe extern "C" fn test_load_value_store_value() {
     free(*ps);
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn test_core_pointer_hooks() {
+    let s = malloc(4 * ::std::mem::size_of::<libc::c_int>() as libc::c_ulong) as *mut libc::c_int;
+    ::std::ptr::write_bytes(s, 0, 4);
+    let end = s.add(4);
+    let last = end.sub(1);
+    let mid = s.wrapping_offset(1).wrapping_add(1).wrapping_sub(1);
+    ::std::ptr::copy_nonoverlapping(s, last, 1);
+    let slice = ::std::slice::from_raw_parts_mut(mid, 2);
+    slice[0] = 1;
+    let sum = ::std::slice::from_raw_parts(s, 4).iter().sum::<libc::c_int>();
+    let non_null = ::std::ptr::NonNull::new_unchecked(last);
+    *non_null.as_ptr() = sum;
+    free(s as *mut libc::c_void);
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn insertion_sort(n: libc::c_int, p: *mut libc::c_int) {
     let mut i: libc::c_int = 1 as libc::c_int;
@@ -744,6 +760,7 @@ unsafe fn main_0(mut argc: libc::c_int, mut argv: *mut *mut libc::c_char) -> lib
     test_store_value();
     test_store_value_field();
     test_load_value_store_value();
+    test_core_pointer_hooks();
     let nums = &mut [2i32, 5i32, 3i32, 1i32, 6i32];
     insertion_sort(nums.len() as libc::c_int, nums as *mut libc::c_int);
     // TODO: this test is broken on Darwin because the fields structure fields
@@ -46,6 +46,7 @@ use rustc_middle::ty::TyCtxt;
 use rustc_middle::ty::TyKind;
 use rustc_middle::ty::WithOptConstParam;
 use rustc_span::{Span, Symbol};
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::env;
@@ -789,18 +790,26 @@ fn run(tcx: TyCtxt) {
 
     // Load permission info from PDG
     let pdg_compare = env::var("C2RUST_ANALYZE_COMPARE_PDG").as_deref() == Ok("1");
+    // In hybrid mode, the PDG only contributes sound updates up front, and the evidence it
+    // collects is used to refine the static results after analysis.
+    let pdg_hybrid = env::var("C2RUST_ANALYZE_PDG_HYBRID").as_deref() == Ok("1");
+    let mut pdg_evidence = None;
     // In compare mode, we load the PDG for comparison after analysis, not before.
     if !pdg_compare {
         if let Some(pdg_file_path) = std::env::var_os("PDG_FILE") {
-            pdg_update_permissions(
+            let evidence = pdg_update_permissions(
                 &mut gacx,
                 &all_fn_ldids,
                 &mut func_info,
                 &mut asn,
                 &mut updates_forbidden,
                 skip_borrowck_everywhere,
+                pdg_hybrid,
                 pdg_file_path,
             );
+            if pdg_hybrid {
+                pdg_evidence = Some(evidence);
+            }
         }
     }
 
@@ -876,99 +885,44 @@ fn run(tcx: TyCtxt) {
 
     apply_test_attr_fail_before_analysis(&mut gacx, &all_fn_ldids);
     apply_test_attr_force_non_null_args(&mut gacx, &all_fn_ldids, &mut asn, &mut updates_forbidden);
+    apply_restrict_attrs(&mut gacx, &all_fn_ldids, &mut updates_forbidden);
 
     debug!("=== ADT Metadata ===");
     debug!("{:?}", gacx.adt_metadata);
 
-    let mut loop_count = 0;
-    loop {
-        // Loop until the global assignment reaches a fixpoint.  The inner loop also runs until a
-        // fixpoint, but it only considers a single function at a time.  The inner loop for one
-        // function can affect other functions by updating the `Assignment`, so we also need the
-        // outer loop, which runs until the `Assignment` converges as well.
-        loop_count += 1;
-        let old_gasn = asn.perms.as_slice()[..gacx.num_global_pointers()].to_owned();
-
-        for &ldid in &all_fn_ldids {
-            if gacx.fn_analysis_invalid(ldid.to_def_id()) {
-                continue;
-            }
-
-            let skip_borrowck =
-                skip_borrowck_everywhere || util::has_test_attr(tcx, ldid, TestAttr::SkipBorrowck);
-
-            let info = func_info.get_mut(&ldid).unwrap();
-            let ldid_const = WithOptConstParam::unknown(ldid);
-            let name = tcx.item_name(ldid.to_def_id());
-            let mir = tcx.mir_built(ldid_const);
-            let mir = mir.borrow();
-
-            let field_ltys = gacx.field_ltys.clone();
-            let acx = gacx.function_context_with_data(&mir, info.acx_data.take());
-
-            let r = panic_detail::catch_unwind(AssertUnwindSafe(|| {
-                // `dataflow.propagate` and `borrowck_mir` both run until the assignment converges
-                // on a fixpoint, so there's no need to do multiple iterations here.
-                info.dataflow.propagate(&mut asn.perms, &updates_forbidden);
-
-                if !skip_borrowck {
-                    borrowck::borrowck_mir(
-                        &acx,
-                        &info.dataflow,
-                        &mut asn.perms_mut(),
-                        &updates_forbidden,
-                        name.as_str(),
-                        &mir,
-                        field_ltys,
-                    );
-                }
-            }));
-
-            info.acx_data.set(acx.into_data());
-
-            match r {
-                Ok(()) => {}
-                Err(pd) => {
-                    gacx.mark_fn_failed(
-                        ldid.to_def_id(),
-                        DontRewriteFnReason::BORROWCK_INVALID,
-                        pd,
-                    );
-                    continue;
-                }
-            }
-        }
-
-        let mut num_changed = 0;
-        for (i, &old) in old_gasn.iter().enumerate() {
-            let ptr = PointerId::global(i as u32);
-
-            if skip_borrowck_everywhere {
-                asn.perms[ptr].insert(PermissionSet::UNIQUE);
-            }
-
-            let new = asn.perms[ptr];
-            if old != new {
-                let added = new & !old;
-                let removed = old & !new;
-                let kept = old & new;
-                debug!(
-                    "changed {:?}: added {:?}, removed {:?}, kept {:?}",
-                    ptr, added, removed, kept
-                );
-                num_changed += 1;
-            }
-        }
-        debug!(
-            "iteration {}: {} global pointers changed",
-            loop_count, num_changed
-        );
+    let pdg_initial_asn = pdg_evidence.as_ref().map(|_| asn.clone());
+    solve_permissions(
+        &mut gacx,
+        &all_fn_ldids,
+        &mut func_info,
+        &mut asn,
+        &updates_forbidden,
+        skip_borrowck_everywhere,
+    );
 
-        if &asn.perms.as_slice()[..gacx.num_global_pointers()] == &old_gasn {
-            break;
+    // In hybrid mode, the static results above are the baseline.  Dynamic evidence can only add
+    // back `NON_NULL` and `UNIQUE` that the static analysis removed, after which we solve again so
+    // the refinements propagate consistently.
+    if let (Some(evidence), Some(initial_asn)) = (&pdg_evidence, pdg_initial_asn) {
+        let mut no_perms = GlobalPointerTable::new(gacx.num_total_pointers());
+        for info in func_info.values() {
+            info.dataflow.collect_no_perms(&mut no_perms);
+        }
+        let refinements = pdg_hybrid_refinements(&asn, &no_perms, evidence);
+        if !refinements.is_empty() {
+            asn = initial_asn;
+            apply_pdg_hybrid_refinements(&mut asn, &mut updates_forbidden, &refinements);
+            solve_permissions(
+                &mut gacx,
+                &all_fn_ldids,
+                &mut func_info,
+                &mut asn,
+                &updates_forbidden,
+                skip_borrowck_everywhere,
+            );
+            clear_revoked_dyn_flags(&mut asn, &refinements);
         }
     }
-    info!("reached fixpoint in {} iterations", loop_count);
 
     // Do final processing on each function.
     for &ldid in &all_fn_ldids {
@@ -2191,6 +2145,108 @@ fn make_sig_fixed(asn: &mut Assignment, lsig: &LFnSig) {
     }
 }
 
+/// Run the dataflow solver and borrowck analysis on all functions until the global
+/// `Assignment` reaches a fixpoint.
+fn solve_permissions<'tcx>(
+    gacx: &mut GlobalAnalysisCtxt<'tcx>,
+    all_fn_ldids: &[LocalDefId],
+    func_info: &mut HashMap<LocalDefId, FuncInfo<'tcx>>,
+    asn: &mut Assignment,
+    updates_forbidden: &GlobalPointerTable<PermissionSet>,
+    skip_borrowck_everywhere: bool,
+) {
+    let tcx = gacx.tcx;
+    let mut loop_count = 0;
+    loop {
+        // Loop until the global assignment reaches a fixpoint.  The inner loop also runs until a
+        // fixpoint, but it only considers a single function at a time.  The inner loop for one
+        // function can affect other functions by updating the `Assignment`, so we also need the
+        // outer loop, which runs until the `Assignment` converges as well.
+        loop_count += 1;
+        let old_gasn = asn.perms.as_slice()[..gacx.num_global_pointers()].to_owned();
+
+        for &ldid in all_fn_ldids {
+            if gacx.fn_analysis_invalid(ldid.to_def_id()) {
+                continue;
+            }
+
+            let skip_borrowck =
+                skip_borrowck_everywhere || util::has_test_attr(tcx, ldid, TestAttr::SkipBorrowck);
+
+            let info = func_info.get_mut(&ldid).unwrap();
+            let ldid_const = WithOptConstParam::unknown(ldid);
+            let name = tcx.item_name(ldid.to_def_id());
+            let mir = tcx.mir_built(ldid_const);
+            let mir = mir.borrow();
+
+            let field_ltys = gacx.field_ltys.clone();
+            let acx = gacx.function_context_with_data(&mir, info.acx_data.take());
+
+            let r = panic_detail::catch_unwind(AssertUnwindSafe(|| {
+                // `dataflow.propagate` and `borrowck_mir` both run until the assignment converges
+                // on a fixpoint, so there's no need to do multiple iterations here.
+                info.dataflow.propagate(&mut asn.perms, updates_forbidden);
+
+                if !skip_borrowck {
+                    borrowck::borrowck_mir(
+                        &acx,
+                        &info.dataflow,
+                        &mut asn.perms_mut(),
+                        updates_forbidden,
+                        name.as_str(),
+                        &mir,
+                        field_ltys,
+                    );
+                }
+            }));
+
+            info.acx_data.set(acx.into_data());
+
+            match r {
+                Ok(()) => {}
+                Err(pd) => {
+                    gacx.mark_fn_failed(
+                        ldid.to_def_id(),
+                        DontRewriteFnReason::BORROWCK_INVALID,
+                        pd,
+                    );
+                    continue;
+                }
+            }
+        }
+
+        let mut num_changed = 0;
+        for (i, &old) in old_gasn.iter().enumerate() {
+            let ptr = PointerId::global(i as u32);
+
+            if skip_borrowck_everywhere {
+                asn.perms[ptr].insert(PermissionSet::UNIQUE);
+            }
+
+            let new = asn.perms[ptr];
+            if old != new {
+                let added = new & !old;
+                let removed = old & !new;
+                let kept = old & new;
+                debug!(
+                    "changed {:?}: added {:?}, removed {:?}, kept {:?}",
+                    ptr, added, removed, kept
+                );
+                num_changed += 1;
+            }
+        }
+        debug!(
+            "iteration {}: {} global pointers changed",
+            loop_count, num_changed
+        );
+
+        if &asn.perms.as_slice()[..gacx.num_global_pointers()] == &old_gasn {
+            break;
+        }
+    }
+    info!("reached fixpoint in {} iterations", loop_count);
+}
+
 /// For testing, putting #[c2rust_analyze_test::fail_before_analysis] on a function marks it as
 /// failed at this point.
 fn apply_test_attr_fail_before_analysis(
@@ -2237,6 +2293,36 @@ fn apply_test_attr_force_non_null_args(
     }
 }
 
+/// `#[c2rust::restrict(..)]` on a function lists the arguments that were `restrict`-qualified in
+/// C.  `restrict` is a promise that the object an argument points to isn't accessed through any
+/// pointer not derived from it while the function runs, so aliasing among the caller's pointers
+/// shouldn't take `UNIQUE` away from the argument.  Adding `UNIQUE` to `updates_forbidden` keeps
+/// dataflow from propagating its removal into the argument, while borrowck still removes it where
+/// the argument's own uses conflict.
+fn apply_restrict_attrs(
+    gacx: &mut GlobalAnalysisCtxt,
+    all_fn_ldids: &[LocalDefId],
+    updates_forbidden: &mut GlobalPointerTable<PermissionSet>,
+) {
+    let tcx = gacx.tcx;
+    for &ldid in all_fn_ldids {
+        let lsig = &gacx.fn_sigs[&ldid.to_def_id()];
+        for idx in util::restrict_args(tcx, ldid) {
+            // Only the outermost pointer is restrict-qualified
+            let ptr = match lsig.inputs.get(idx) {
+                Some(arg_lty) => arg_lty.label,
+                None => continue,
+            };
+            if !ptr.is_none() {
+                debug!("{:?}: keeping UNIQUE for restrict arg {}", ldid, idx);
+                updates_forbidden[ptr].insert(PermissionSet::UNIQUE);
+            }
+        }
+    }
+}
+
+/// Load the PDG from `pdg_file_path` and apply its permission updates.  Returns the dynamic
+/// evidence observed for each `PointerId`, which is used for refinements in hybrid mode.
 fn pdg_update_permissions<'tcx>(
     gacx: &mut GlobalAnalysisCtxt<'tcx>,
     all_fn_ldids: &[LocalDefId],
@@ -2244,11 +2330,14 @@ fn pdg_update_permissions<'tcx>(
     asn: &mut Assignment,
     updates_forbidden: &mut GlobalPointerTable<PermissionSet>,
     skip_borrowck_everywhere: bool,
+    hybrid: bool,
     pdg_file_path: impl AsRef<Path>,
-) {
+) -> HashMap<PointerId, PdgEvidence> {
+    // Hybrid mode replaces the unsound override with refinements applied after analysis.
     let allow_unsound =
-        env::var("C2RUST_ANALYZE_PDG_ALLOW_UNSOUND").map_or(false, |val| &val == "1");
+        !hybrid && env::var("C2RUST_ANALYZE_PDG_ALLOW_UNSOUND").map_or(false, |val| &val == "1");
 
+    let mut evidence = HashMap::<PointerId, PdgEvidence>::new();
     pdg_update_permissions_with_callback(
         gacx,
         all_fn_ldids,
@@ -2257,6 +2346,11 @@ fn pdg_update_permissions<'tcx>(
         updates_forbidden,
         pdg_file_path,
         |asn, updates_forbidden, _ldid, ptr, _ptr_is_global, node_info, node_is_non_null| {
+            evidence
+                .entry(ptr)
+                .or_default()
+                .observe(node_info, node_is_non_null);
+
             let old_perms = asn.perms()[ptr];
             let mut perms = old_perms;
             if !node_is_non_null {
@@ -2300,6 +2394,98 @@ fn pdg_update_permissions<'tcx>(
             }
         },
     );
+    evidence
+}
+
+/// Dynamic facts about a single `PointerId`, gathered from all of the PDG nodes that store to it.
+#[derive(Clone, Copy, Debug, Default)]
+struct PdgEvidence {
+    saw_null: bool,
+    saw_non_null: bool,
+    saw_unique: bool,
+    saw_non_unique: bool,
+}
+
+impl PdgEvidence {
+    fn observe(&mut self, node_info: Option<&NodeInfo>, node_is_non_null: bool) {
+        if node_is_non_null {
+            self.saw_non_null = true;
+        } else {
+            self.saw_null = true;
+        }
+        match node_info {
+            Some(info) if info.unique => self.saw_unique = true,
+            Some(_) => self.saw_non_unique = true,
+            None => {}
+        }
+    }
+
+    /// The permissions that the dynamic evidence supports: `NON_NULL` if the pointer was never
+    /// observed to be null, and `UNIQUE` if no conflicting access was observed.  Pointers that
+    /// never appeared in the PDG have no evidence either way.
+    fn supported_perms(&self) -> PermissionSet {
+        let mut perms = PermissionSet::empty();
+        if self.saw_non_null && !self.saw_null {
+            perms.insert(PermissionSet::NON_NULL);
+        }
+        if self.saw_unique && !self.saw_non_unique {
+            perms.insert(PermissionSet::UNIQUE);
+        }
+        perms
+    }
+}
+
+/// Compute the permissions to add back to each pointer in hybrid mode: those that the static
+/// analysis removed, but that the dynamic evidence supports.  `FIXED` pointers are never refined,
+/// and neither are permissions that a dataflow constraint rules out directly (`no_perms`), such as
+/// `NON_NULL` on the result of `ptr::null()`.
+fn pdg_hybrid_refinements(
+    asn: &Assignment,
+    no_perms: &GlobalPointerTable<PermissionSet>,
+    evidence: &HashMap<PointerId, PdgEvidence>,
+) -> BTreeMap<PointerId, PermissionSet> {
+    evidence
+        .iter()
+        .filter(|&(&ptr, _)| !asn.flags[ptr].contains(FlagSet::FIXED))
+        .map(|(&ptr, ev)| (ptr, ev.supported_perms() & !asn.perms[ptr] & !no_perms[ptr]))
+        .filter(|&(_, perms)| !perms.is_empty())
+        .collect()
+}
+
+/// Add each of the hybrid `refinements` to `asn`, forbid dataflow from removing them again, and
+/// flag the pointers whose permissions now rely on dynamic evidence.  Borrowck can still remove
+/// `UNIQUE` from a pointer whose loans conflict, regardless of `updates_forbidden`.
+fn apply_pdg_hybrid_refinements(
+    asn: &mut Assignment,
+    updates_forbidden: &mut GlobalPointerTable<PermissionSet>,
+    refinements: &BTreeMap<PointerId, PermissionSet>,
+) {
+    for (&ptr, &perms) in refinements {
+        eprintln!("pdg: hybrid: assuming {perms:?} for {ptr:?} from dynamic evidence");
+        asn.perms_mut()[ptr].insert(perms);
+        updates_forbidden[ptr].insert(perms);
+        if perms.contains(PermissionSet::NON_NULL) {
+            asn.flags[ptr].insert(FlagSet::DYN_NON_NULL);
+        }
+        if perms.contains(PermissionSet::UNIQUE) {
+            asn.flags[ptr].insert(FlagSet::DYN_UNIQUE);
+        }
+    }
+}
+
+/// Clear the `DYN_NON_NULL` and `DYN_UNIQUE` flags from pointers that lost the corresponding
+/// permission when solving again after `apply_pdg_hybrid_refinements`.  Such pointers no longer
+/// rely on dynamic evidence, so there's nothing to check.
+fn clear_revoked_dyn_flags(asn: &mut Assignment, refinements: &BTreeMap<PointerId, PermissionSet>) {
+    for &ptr in refinements.keys() {
+        let perms = asn.perms[ptr];
+        if !perms.contains(PermissionSet::NON_NULL) {
+            asn.flags[ptr].remove(FlagSet::DYN_NON_NULL);
+        }
+        if !perms.contains(PermissionSet::UNIQUE) {
+            asn.flags[ptr].remove(FlagSet::DYN_UNIQUE);
+        }
+    }
 }
 
 /// Load PDG from `pdg_file_path` and update permissions.
@@ -2827,3 +3013,74 @@ impl rustc_driver::Callbacks for AnalysisCallbacks {
         rustc_driver::Compilation::Continue
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn evidence(saw_null: bool, saw_unique: bool, saw_non_unique: bool) -> PdgEvidence {
+        PdgEvidence {
+            saw_null,
+            saw_non_null: true,
+            saw_unique,
+            saw_non_unique,
+        }
+    }
+
+    /// Test that hybrid mode only adds back `NON_NULL` and `UNIQUE` that the dynamic evidence
+    /// supports and no dataflow constraint rules out, and flags the pointers that now rely on it.
+    #[test]
+    fn pdg_hybrid_refinement_perms_and_flags() {
+        let [non_null, unique, conflicting, fixed, unchanged, null] =
+            [0, 1, 2, 3, 4, 5].map(PointerId::global);
+        let mut asn = Assignment::new(6, PermissionSet::READ, FlagSet::empty());
+        asn.flags[fixed].insert(FlagSet::FIXED);
+        asn.perms[unchanged].insert(PermissionSet::NON_NULL | PermissionSet::UNIQUE);
+        let mut no_perms = GlobalPointerTable::<PermissionSet>::new(6);
+        no_perms[null].insert(PermissionSet::NON_NULL);
+
+        let evidence = HashMap::from([
+            (non_null, evidence(false, false, true)),
+            (unique, evidence(true, true, false)),
+            (conflicting, evidence(true, true, true)),
+            (fixed, evidence(false, true, false)),
+            (unchanged, evidence(false, true, false)),
+            (null, evidence(false, false, true)),
+        ]);
+        let refinements = pdg_hybrid_refinements(&asn, &no_perms, &evidence);
+        assert_eq!(
+            refinements,
+            BTreeMap::from([
+                (non_null, PermissionSet::NON_NULL),
+                (unique, PermissionSet::UNIQUE),
+            ])
+        );
+
+        let mut updates_forbidden = GlobalPointerTable::new(6);
+        apply_pdg_hybrid_refinements(&mut asn, &mut updates_forbidden, &refinements);
+        assert_eq!(
+            asn.perms[non_null],
+            PermissionSet::READ | PermissionSet::NON_NULL
+        );
+        assert_eq!(asn.flags[non_null], FlagSet::DYN_NON_NULL);
+        assert_eq!(updates_forbidden[non_null], PermissionSet::NON_NULL);
+        assert_eq!(
+            asn.perms[unique],
+            PermissionSet::READ | PermissionSet::UNIQUE
+        );
+        assert_eq!(asn.flags[unique], FlagSet::DYN_UNIQUE);
+        assert_eq!(updates_forbidden[unique], PermissionSet::UNIQUE);
+        for ptr in [conflicting, fixed, unchanged, null] {
+            assert!(!asn.flags[ptr].intersects(FlagSet::DYN_NON_NULL | FlagSet::DYN_UNIQUE));
+            assert!(updates_forbidden[ptr].is_empty());
+        }
+        assert_eq!(asn.perms[conflicting], PermissionSet::READ);
+        assert_eq!(asn.perms[fixed], PermissionSet::READ);
+
+        // Borrowck can still revoke `UNIQUE`, after which there's nothing left to check.
+        asn.perms[unique].remove(PermissionSet::UNIQUE);
+        clear_revoked_dyn_flags(&mut asn, &refinements);
+        assert_eq!(asn.flags[unique], FlagSet::empty());
+        assert_eq!(asn.flags[non_null], FlagSet::DYN_NON_NULL);
+    }
+}
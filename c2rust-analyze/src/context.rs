@@ -201,6 +201,17 @@ bitflags! {
         /// cross an FFI boundary, and for arguments and return values of functions we can't
         /// rewrite.
         const FIXED = 0x0002;
+
+        /// `NON_NULL` was added to this pointer in PDG hybrid mode, based only on dynamic
+        /// evidence: the static analysis couldn't prove it, but no null value was observed at
+        /// runtime.  Rewrites insert debug assertions where this assumption can be checked.
+        const DYN_NON_NULL = 0x0004;
+
+        /// `UNIQUE` was added to this pointer in PDG hybrid mode, based only on dynamic evidence:
+        /// the static analysis couldn't prove it, but no conflicting access was observed at
+        /// runtime.  Rewritten calls and shims check in debug builds that such an argument doesn't
+        /// alias any other pointer argument.
+        const DYN_UNIQUE = 0x0008;
     }
 }
 
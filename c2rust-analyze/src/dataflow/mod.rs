@@ -57,6 +57,15 @@ impl DataflowConstraints {
         self.constraints.push(Constraint::NoPerms(ptr, perms));
     }
 
+    /// Add the permissions that `NoPerms` constraints rule out for each pointer to `no_perms`.
+    pub fn collect_no_perms(&self, no_perms: &mut GlobalPointerTable<PermissionSet>) {
+        for c in &self.constraints {
+            if let Constraint::NoPerms(ptr, perms) = *c {
+                no_perms[ptr].insert(perms);
+            }
+        }
+    }
+
     /// Update the pointer permissions in `hypothesis` to satisfy these constraints.
     ///
     /// If `restrict_updates[ptr]` has some flags set, then those flags will be left unchanged in
@@ -292,7 +301,7 @@ impl DataflowConstraints {
         impl PropagateRules<FlagSet> for Rules<'_> {
             fn subset(
                 &mut self,
-                _a_ptr: PointerId,
+                a_ptr: PointerId,
                 a_val: &FlagSet,
                 b_ptr: PointerId,
                 b_val: &FlagSet,
@@ -313,6 +322,13 @@ impl DataflowConstraints {
                 if b_perms.contains(PermissionSet::WRITE | PermissionSet::UNIQUE) {
                     b_flags.remove(FlagSet::CELL);
                 }
+                // Normally `a` can only have `WRITE` and `UNIQUE` if `b` does too, but `UNIQUE`
+                // added in PDG hybrid mode is kept even when `b` lacks it.  Remove `CELL` on this
+                // side as well, so the two constraints `a` is part of can't keep flipping it.
+                let a_perms = self.perms[a_ptr];
+                if a_perms.contains(PermissionSet::WRITE | PermissionSet::UNIQUE) {
+                    a_flags.remove(FlagSet::CELL);
+                }
 
                 (a_flags, b_flags)
             }
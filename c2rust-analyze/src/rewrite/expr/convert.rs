@@ -311,6 +311,51 @@ impl<'tcx> ConvertVisitor<'tcx> {
                 Rewrite::Block(stmts, Some(Box::new(format_rewrite!("dest"))))
             }
 
+            mir_op::RewriteKind::DebugAssertDistinctArgs {
+                ref pairs,
+                ref raw_args,
+            } => {
+                // `f(p, q)` to `{ let (arg0, arg1) = (p, q); debug_assert!(...); f(arg0, arg1) }`
+                assert!(matches!(hir_rw, Rewrite::Identity));
+                let (func, num_args) = match ex.kind {
+                    ExprKind::Call(func, args) => (func, args.len()),
+                    // Method calls can't be rebuilt from their arguments this way, so they go
+                    // unchecked.
+                    _ => return hir_rw,
+                };
+                let callee = self
+                    .tcx
+                    .sess
+                    .source_map()
+                    .span_to_snippet(func.span)
+                    .unwrap_or_else(|e| panic!("no snippet for callee {func:?}: {e:?}"));
+
+                let mut stmts = Vec::with_capacity(1 + pairs.len());
+                stmts.push(Rewrite::Let(
+                    (0..num_args)
+                        .map(|i| (format!("arg{i}"), self.get_subexpr(ex, i)))
+                        .collect(),
+                ));
+                let addr = |i: usize| {
+                    if raw_args.contains(&i) {
+                        format!("arg{i} as *const u8")
+                    } else {
+                        format!("&*arg{i} as *const _ as *const u8")
+                    }
+                };
+                for &(i, j) in pairs {
+                    stmts.push(Rewrite::Print(format!(
+                        r#"debug_assert!({} != {}, "argument {i} of {callee} was assumed unique from dynamic analysis, but aliases argument {j}")"#,
+                        addr(i),
+                        addr(j),
+                    )));
+                }
+                let args = (0..num_args)
+                    .map(|i| Rewrite::Text(format!("arg{i}")))
+                    .collect();
+                Rewrite::Block(stmts, Some(Box::new(Rewrite::Call(callee, args))))
+            }
+
             mir_op::RewriteKind::MemsetZeroize {
                 ref zero_ty,
                 ref elem_ty,
@@ -952,6 +997,19 @@ pub fn convert_cast_rewrite(kind: &mir_op::RewriteKind, hir_rw: Rewrite) -> Rewr
             let rw_pl = Rewrite::Deref(Box::new(hir_rw));
             Rewrite::Ref(Box::new(rw_pl), mutbl_from_bool(mutbl))
         }
+        mir_op::RewriteKind::DebugAssertNonNull => {
+            // `p` to `{ let ptr = p; debug_assert!(!ptr.is_null()); ptr }`
+            Rewrite::Block(
+                vec![
+                    Rewrite::Let1("ptr".into(), Box::new(hir_rw)),
+                    Rewrite::Print(
+                        r#"debug_assert!(!ptr.is_null(), "pointer was assumed non-null from dynamic analysis")"#
+                            .into(),
+                    ),
+                ],
+                Some(Box::new(Rewrite::Print("ptr".into()))),
+            )
+        }
 
         mir_op::RewriteKind::CellNew => {
             // `x` to `Cell::new(x)`
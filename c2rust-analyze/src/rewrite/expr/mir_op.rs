@@ -161,6 +161,17 @@ pub enum RewriteKind {
     CastRawToRaw { to_mutbl: bool },
     /// Cast `*const T` to `& T` or `*mut T` to `&mut T`.
     UnsafeCastRawToRef { mutbl: bool },
+    /// Check that a raw pointer is non-null in debug builds, leaving its value unchanged.  Used
+    /// when `NON_NULL` was only inferred from dynamic evidence (`FlagSet::DYN_NON_NULL`).
+    DebugAssertNonNull,
+    /// Check that the pointer arguments of a call don't alias in debug builds, then make the call.
+    /// Each `(i, j)` in `pairs` means argument `i`, which is only assumed to be `UNIQUE` based on
+    /// dynamic evidence (`FlagSet::DYN_UNIQUE`), must not alias argument `j`.  Arguments listed in
+    /// `raw_args` are still raw pointers after rewriting.
+    DebugAssertDistinctArgs {
+        pairs: Vec<(usize, usize)>,
+        raw_args: Vec<usize>,
+    },
     /// Cast *mut T to *const Cell<T>
     CastRawMutToCellPtr { ty: String },
 
@@ -305,6 +316,53 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
         self.enter(SubLoc::Dest, f)
     }
 
+    /// Emit a debug assertion on a call to a local function if any of the callee's pointer
+    /// arguments `arg_ltys` is only assumed to be `UNIQUE` based on dynamic evidence.  The
+    /// assertion checks that such an argument doesn't alias any other pointer argument.
+    fn emit_dyn_unique_arg_checks(&mut self, arg_ltys: &[LTy<'tcx>]) {
+        // Returns `Some(is_raw)` if we can take the address of an argument of rewritten type
+        // `lty` without consuming it, or `None` if it might be `None` or dynamically owned.
+        let addr_kind = |lty: LTy<'tcx>| -> Option<bool> {
+            if lty.label.is_none() {
+                return None;
+            }
+            if self.flags[lty.label].contains(FlagSet::FIXED) {
+                return Some(lty.ty.is_unsafe_ptr());
+            }
+            let desc =
+                type_desc::perms_to_desc(lty.ty, self.perms[lty.label], self.flags[lty.label]);
+            if desc.option || desc.dyn_owned {
+                return None;
+            }
+            Some(matches!(desc.own, Ownership::Raw | Ownership::RawMut))
+        };
+
+        let mut pairs = Vec::new();
+        for (i, &lty) in arg_ltys.iter().enumerate() {
+            if lty.label.is_none()
+                || !self.flags[lty.label].contains(FlagSet::DYN_UNIQUE)
+                || addr_kind(lty).is_none()
+            {
+                continue;
+            }
+            for (j, &other_lty) in arg_ltys.iter().enumerate() {
+                if j != i && addr_kind(other_lty).is_some() && !pairs.contains(&(j, i)) {
+                    pairs.push((i, j));
+                }
+            }
+        }
+        if pairs.is_empty() {
+            return;
+        }
+        let raw_args = arg_ltys
+            .iter()
+            .enumerate()
+            .filter(|&(_, &lty)| addr_kind(lty) == Some(true))
+            .map(|(i, _)| i)
+            .collect();
+        self.emit(RewriteKind::DebugAssertDistinctArgs { pairs, raw_args });
+    }
+
     fn enter_rvalue<F: FnOnce(&mut Self) -> R, R>(&mut self, f: F) -> R {
         self.enter(SubLoc::Rvalue, f)
     }
@@ -640,6 +698,10 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
                         // TODO: handle substs (if nonempty)
                         if let Some(lsig) = self.acx.gacx.fn_sigs.get(&def_id) {
                             self.enter_rvalue(|v| {
+                                v.emit_dyn_unique_arg_checks(
+                                    &lsig.inputs[..args.len().min(lsig.inputs.len())],
+                                );
+
                                 for (i, op) in args.iter().enumerate() {
                                     if let Some(&lty) = lsig.inputs.get(i) {
                                         v.enter_call_arg(i, |v| v.visit_operand(op, Some(lty)));
@@ -1871,7 +1933,9 @@ where
                 _ => None,
             },
             Ownership::Cell => match to.own {
-                Ownership::RawMut | Ownership::Raw if !early => {
+                // `Cell` to `Mut` only happens for pointers that are assumed `UNIQUE` based on
+                // dynamic evidence in PDG hybrid mode.  It goes through `RawMut`.
+                Ownership::RawMut | Ownership::Raw | Ownership::Mut if !early => {
                     (self.emit)(RewriteKind::AsPtr);
                     Some(Ownership::RawMut)
                 }
@@ -1918,6 +1982,26 @@ where
         })
     }
 
+    /// Emit a debug assertion before a cast from raw `from` to non-optional `to` if the
+    /// destination pointer `to_ptr` is only assumed to be `NON_NULL` based on dynamic evidence.
+    pub fn emit_dyn_non_null_check(
+        &mut self,
+        from: TypeDesc<'tcx>,
+        to_ptr: PointerId,
+        to: TypeDesc<'tcx>,
+    ) {
+        let is_raw = |own| matches!(own, Ownership::Raw | Ownership::RawMut);
+        if !to_ptr.is_none()
+            && self.flags[to_ptr].contains(FlagSet::DYN_NON_NULL)
+            && is_raw(from.own)
+            && !from.option
+            && !is_raw(to.own)
+            && !to.option
+        {
+            (self.emit)(RewriteKind::DebugAssertNonNull);
+        }
+    }
+
     pub fn build_cast_lty_desc(&mut self, from_lty: LTy<'tcx>, to: TypeDesc<'tcx>) {
         let from = type_desc::perms_to_desc_with_pointee(
             self.tcx,
@@ -1937,6 +2021,7 @@ where
             self.perms[to_lty.label],
             self.flags[to_lty.label],
         );
+        self.emit_dyn_non_null_check(from, to_lty.label, to);
         self.build_cast_desc_desc(from, to);
     }
 
@@ -1964,6 +2049,7 @@ where
             (false, false) => {
                 let from = self.lty_to_desc(from_lty);
                 let to = self.lty_to_desc(to_lty);
+                self.emit_dyn_non_null_check(from, to_lty.label, to);
                 self.build_cast_desc_desc(from, to);
             }
 
@@ -1974,7 +2060,15 @@ where
 
             (true, false) => {
                 let to = self.lty_to_desc(to_lty);
-                self.build_cast_lty_desc(from_lty, to);
+                let from = type_desc::perms_to_desc_with_pointee(
+                    self.tcx,
+                    to.pointee_ty,
+                    from_lty.ty,
+                    self.perms[from_lty.label],
+                    self.flags[from_lty.label],
+                );
+                self.emit_dyn_non_null_check(from, to_lty.label, to);
+                self.build_cast_desc_desc(from, to);
             }
 
             (true, true) => {
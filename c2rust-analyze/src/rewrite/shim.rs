@@ -208,19 +208,37 @@ pub fn gen_shim_definition_rewrite<'tcx>(
     // valid `fn_sigs` entries.
     let lsig = gacx.fn_sigs[&def_id];
 
-    // 1 cast per arg, 1 call, 1 cast for the result.  The final result is returned using the
-    // trailing expression of the block.
+    // 1 cast per arg, 1 call, 1 cast for the result, plus any debug assertions for arguments.  The
+    // final result is returned using the trailing expression of the block.
     let mut stmts = Vec::with_capacity(arg_tys.len() + 2);
 
     // Generate `let safe_arg0 = arg0 as ...;` for each argument.
     let mut arg_exprs = Vec::with_capacity(arg_tys.len());
     for (i, arg_lty) in lsig.inputs.iter().enumerate() {
+        // If this argument is only assumed to be unique based on dynamic evidence, check in debug
+        // builds that it doesn't alias any of the other pointer arguments.
+        let ptr = arg_lty.label;
+        if !ptr.is_none() && asn.flags[ptr].contains(FlagSet::DYN_UNIQUE) {
+            for (j, other_lty) in lsig.inputs.iter().enumerate() {
+                if j == i || other_lty.label.is_none() {
+                    continue;
+                }
+                stmts.push(Rewrite::Print(format!(
+                    r#"debug_assert!(arg{i} as *const u8 != arg{j} as *const u8, "arg{i} of {} was assumed unique from dynamic analysis, but aliases arg{j}")"#,
+                    owner_node.ident().unwrap().as_str(),
+                )));
+            }
+        }
+
         let mut hir_rw = Rewrite::FnArg(i);
 
         if let Some((arg_desc, fixed_desc)) = lty_to_desc_pair(tcx, asn, arg_lty) {
             let mut cast_builder = CastBuilder::new(tcx, &asn.perms, &asn.flags, |rk| {
                 hir_rw = expr::convert_cast_rewrite(&rk, mem::take(&mut hir_rw));
             });
+            // If this argument is only assumed to be non-null based on dynamic evidence, check
+            // that assumption in debug builds before casting the raw pointer to a reference.
+            cast_builder.emit_dyn_non_null_check(fixed_desc, ptr, arg_desc);
             match cast_builder.try_build_cast_desc_desc(fixed_desc, arg_desc) {
                 Ok(()) => {}
                 Err(e) => {
@@ -590,6 +590,38 @@ pub fn has_test_attr(tcx: TyCtxt, ldid: LocalDefId, attr: TestAttr) -> bool {
     false
 }
 
+/// Get the indices of the arguments listed in the function's `#[c2rust::restrict(..)]`
+/// attribute, which `c2rust transpile --annotate-restrict` adds for `restrict`-qualified pointer
+/// parameters.
+pub fn restrict_args(tcx: TyCtxt, ldid: LocalDefId) -> Vec<usize> {
+    let tool_sym = Symbol::intern("c2rust");
+    let name_sym = Symbol::intern("restrict");
+
+    let arg_names = tcx.fn_arg_names(ldid.to_def_id());
+    let mut args = Vec::new();
+    for attr in tcx.get_attrs_unchecked(ldid.to_def_id()) {
+        let path = match attr.kind {
+            AttrKind::Normal(ref item, _) => &item.path,
+            AttrKind::DocComment(..) => continue,
+        };
+        match &path.segments[..] {
+            [a, b] if a.ident.name == tool_sym && b.ident.name == name_sym => {}
+            _ => continue,
+        }
+        for nested in attr.meta_item_list().unwrap_or_default() {
+            let ident = match nested.ident() {
+                Some(ident) => ident,
+                None => continue,
+            };
+            match arg_names.iter().position(|arg| arg.name == ident.name) {
+                Some(idx) => args.push(idx),
+                None => debug!("{:?}: restrict arg {} not found", ldid, ident),
+            }
+        }
+    }
+    args
+}
+
 /// Check if a [`Body`] is from an `impl` marked `#[automatically_derived]`,
 /// which indicates it came from a `#[derive(...)]`.
 pub fn is_automatically_derived<'tcx>(tcx: TyCtxt<'tcx>, body: &Body<'tcx>) -> bool {
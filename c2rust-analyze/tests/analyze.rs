@@ -5,8 +5,8 @@ use crate::common::test_dir_for;
 use crate::common::Analyze;
 use crate::common::CrateOptions;
 use crate::common::CrateType;
-use fs_err::File;
-use std::path::Path;
+use fs_err::{self as fs, File};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 #[test]
@@ -93,3 +93,113 @@ fn with_pdg_file() {
         Some(crate_options),
     );
 }
+
+/// Instrument and run the crate in `dir`, then build its PDG.  Returns the path of the PDG and the
+/// `-C metadata` hash of the instrumented binary, which `c2rust-analyze` needs to match up the
+/// `DefPathHash`es in the PDG.
+fn generate_pdg(dir: &Path) -> (PathBuf, String) {
+    let dir = dir.canonicalize().unwrap();
+    let repo_dir = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+    let target_dir = dir.join("instrument.target");
+    let _ = fs::remove_dir_all(&target_dir);
+    let exe_dir = target_dir.join("debug");
+    let metadata_path = exe_dir.join("metadata.bc");
+    let event_log_path = exe_dir.join("event.log.bc");
+    let pdg_path = exe_dir.join("pdg.bc");
+
+    let mut cmd = Command::new("cargo");
+    cmd.current_dir(repo_dir)
+        .args(["run", "--bin", "c2rust-instrument", "--", "--metadata"])
+        .arg(&metadata_path)
+        .arg("--runtime-path")
+        .arg(repo_dir.join("analysis/runtime"))
+        .args(["--", "run", "--manifest-path"])
+        .arg(dir.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .env("METADATA_FILE", &metadata_path)
+        .env("INSTRUMENT_RUNTIME", "bg")
+        .env("INSTRUMENT_BACKEND", "log")
+        .env("INSTRUMENT_OUTPUT", &event_log_path)
+        .env("INSTRUMENT_OUTPUT_APPEND", "false");
+    let status = cmd.status().unwrap();
+    assert!(status.success(), "{cmd:?} failed: {status}");
+
+    let mut cmd = Command::new("cargo");
+    cmd.current_dir(repo_dir)
+        .args(["run", "--bin", "c2rust-pdg", "--", "--metadata"])
+        .arg(&metadata_path)
+        .arg("--event-log")
+        .arg(&event_log_path)
+        .arg("--output")
+        .arg(&pdg_path);
+    let status = cmd.status().unwrap();
+    assert!(status.success(), "{cmd:?} failed: {status}");
+
+    // The instrumented binary is `deps/<crate name>-<metadata hash>`.
+    let metadata_hash = fs::read_dir(exe_dir.join("deps"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .find_map(|name| {
+            name.strip_prefix("c2rust_analysis_tests_hybrid-")
+                .filter(|hash| !hash.contains('.'))
+                .map(str::to_owned)
+        })
+        .expect("instrumented binary not found");
+    (pdg_path, metadata_hash)
+}
+
+/// Analyze `analysis/tests/hybrid` with a PDG generated from it, and check that hybrid mode uses
+/// the dynamic evidence to change the rewrite.
+#[test]
+fn with_generated_pdg_hybrid() {
+    let dir = Path::new("../analysis/tests/hybrid");
+    let (pdg_path, metadata_hash) = generate_pdg(dir);
+
+    let analyze = |hybrid: bool| {
+        let crate_options = CrateOptions {
+            crate_type: CrateType::Bin,
+            ..Default::default()
+        };
+        let output_path = Analyze::resolve().run_with(
+            dir.join("src/main.rs"),
+            |cmd| {
+                cmd.args(["--crate-name", "c2rust_analysis_tests_hybrid", "-C"])
+                    .arg(format!("metadata={metadata_hash}"));
+                if hybrid {
+                    cmd.env("PDG_FILE", &pdg_path)
+                        .env("C2RUST_ANALYZE_PDG_HYBRID", "1");
+                }
+            },
+            Some(crate_options),
+        );
+        fs::read_to_string(output_path).unwrap()
+    };
+
+    let static_output = analyze(false);
+    let hybrid_output = analyze(true);
+    assert!(
+        !hybrid_output.contains("pdg: unknown DefPathHash"),
+        "{hybrid_output}"
+    );
+    assert!(
+        hybrid_output.contains("from dynamic evidence"),
+        "{hybrid_output}"
+    );
+
+    // Statically, `p` may be null, but it was never null at runtime.
+    let rewrite_of_p = |output: &str| {
+        output
+            .lines()
+            .find(|line| line.contains("let p ="))
+            .unwrap_or_else(|| panic!("no rewrite of `p`:\n{output}"))
+            .to_owned()
+    };
+    assert_ne!(rewrite_of_p(&static_output), rewrite_of_p(&hybrid_output));
+
+    // Statically, `set_both` may get the same pointer twice, but it never did at runtime.  The
+    // call is checked instead.
+    let message = "argument 0 of set_both was assumed unique from dynamic analysis";
+    assert!(!static_output.contains(message), "{static_output}");
+    assert!(hybrid_output.contains(message), "{hybrid_output}");
+}
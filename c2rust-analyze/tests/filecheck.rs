@@ -66,6 +66,7 @@ define_tests! {
     pointee,
     ptrptr1,
     regions_fixed,
+    restrict,
     rewrite_nullable_box,
     rewrite_paths,
     rewrite_paths_manual_shim,